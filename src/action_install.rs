@@ -5,6 +5,7 @@ use crate::rua_files;
 use crate::tar_check;
 use crate::terminal_util;
 use crate::wrapped;
+use crate::wrapped::MakePkgOptions;
 
 use directories::ProjectDirs;
 use fs_extra::dir::CopyOptions;
@@ -19,7 +20,24 @@ use std::fs;
 use std::fs::ReadDir;
 use std::path::PathBuf;
 
-pub fn install(targets: &[String], dirs: &ProjectDirs, is_offline: bool, asdeps: bool) {
+/// Default value for the `--jobs` flag: the number of concurrently buildable
+/// packages within a single dependency-depth group.
+pub fn default_jobs() -> usize {
+	std::thread::available_parallelism()
+		.map(std::num::NonZeroUsize::get)
+		.unwrap_or(1)
+}
+
+pub fn install(
+	targets: &[String],
+	dirs: &ProjectDirs,
+	is_offline: bool,
+	asdeps: bool,
+	noconfirm: bool,
+	report: bool,
+	makepkg_options: &MakePkgOptions,
+	jobs: usize,
+) {
 	let alpm = pacman::create_alpm();
 	let (split_to_raur, pacman_deps, split_to_depth) =
 		aur_rpc_utils::recursive_info(targets, &alpm).unwrap_or_else(|err| {
@@ -46,7 +64,7 @@ pub fn install(targets: &[String], dirs: &ProjectDirs, is_offline: bool, asdeps:
 		std::process::exit(1)
 	}
 
-	show_install_summary(&pacman_deps, &split_to_depth);
+	show_install_summary(&pacman_deps, &split_to_depth, noconfirm || report);
 	for pkgbase in split_to_pkgbase.values().collect::<HashSet<_>>() {
 		let dir = rua_files::review_dir(dirs, pkgbase);
 		fs::create_dir_all(&dir).unwrap_or_else(|err| {
@@ -62,10 +80,18 @@ pub fn install(targets: &[String], dirs: &ProjectDirs, is_offline: bool, asdeps:
 		split_to_version,
 		is_offline,
 		asdeps,
+		noconfirm,
+		report,
+		makepkg_options,
+		jobs,
 	);
 }
 
-fn show_install_summary(pacman_deps: &IndexSet<String>, aur_packages: &IndexMap<String, i32>) {
+fn show_install_summary(
+	pacman_deps: &IndexSet<String>,
+	aur_packages: &IndexMap<String, i32>,
+	skip_prompt: bool,
+) {
 	if pacman_deps.len() + aur_packages.len() == 1 {
 		return;
 	}
@@ -84,6 +110,9 @@ fn show_install_summary(pacman_deps: &IndexSet<String>, aur_packages: &IndexMap<
 		"{}\n",
 		aur_packages.iter().map(|s| format!("  {}", s.0)).join("\n")
 	);
+	if skip_prompt {
+		return;
+	}
 	loop {
 		eprint!("Proceed? [O]=ok, Ctrl-C=abort. ");
 		let string = terminal_util::read_line_lowercase();
@@ -100,9 +129,13 @@ fn install_all(
 	split_to_version: IndexMap<String, String>,
 	offline: bool,
 	asdeps: bool,
+	noconfirm: bool,
+	report: bool,
+	makepkg_options: &MakePkgOptions,
+	jobs: usize,
 ) {
 	let archive_whitelist = split_to_version
-		.into_iter()
+		.iter()
 		.map(|pair| format!("{}-{}", pair.0, pair.1))
 		.collect::<Vec<_>>();
 	trace!("All expected archive files: {:?}", archive_whitelist);
@@ -123,29 +156,77 @@ fn install_all(
 	let packages: Vec<(String, i32, String)> = packages
 		.unique_by(|(pkgbase, _depth, _split)| pkgbase.to_string())
 		.collect::<Vec<_>>();
+	// `makepkg --needed` only applies together with `-i`, which rua never passes,
+	// so we enforce "don't rebuild a package already at the target version"
+	// ourselves, against rua's own install record rather than makepkg's.
+	let packages: Vec<(String, i32, String)> = if makepkg_options.needed {
+		let installed_packages = rua_files::load_installed_packages(dirs);
+		packages
+			.into_iter()
+			.filter(|(pkgbase, _depth, split)| {
+				let installed = match installed_packages.get(pkgbase) {
+					Some(installed) => installed,
+					None => return true,
+				};
+				let target_version = split_to_version.get(split);
+				match target_version {
+					Some(target_version) if target_version == &installed.version => {
+						debug!("{} is already at version {}, skipping build", pkgbase, target_version);
+						false
+					}
+					_ => true,
+				}
+			})
+			.collect()
+	} else {
+		packages
+	};
 	// once we have a collection of pkgname-s and their depth, proceed straightforwardly.
 	for (depth, packages) in &packages.iter().group_by(|(_pkgbase, depth, _split)| *depth) {
 		let packages = packages.collect::<Vec<&(String, i32, String)>>();
+		// All packages within one depth group are independent of each other (their
+		// dependencies live at strictly greater depth and were already built), so they
+		// can be built concurrently. The interactive tar_check step below still runs
+		// one pkgbase at a time so prompts aren't interleaved.
+		let build_queue = std::sync::Mutex::new(packages.iter().collect::<std::collections::VecDeque<_>>());
+		let worker_count = jobs.max(1).min(packages.len().max(1));
+		// --syncdeps/--rmdeps touch the pacman db lock, so they may only run when
+		// there is a single build in flight for this depth group. Safe only because
+		// `pacman::ensure_pacman_packages_installed(pacman_deps)` above (in `install`)
+		// already installs every pacman dependency makepkg would otherwise sync here,
+		// makedepends included -- see the longer note in `wrapped::makepkg_args`.
+		let serialized = worker_count <= 1;
+		std::thread::scope(|scope| {
+			for _ in 0..worker_count {
+				scope.spawn(|| loop {
+					let next = build_queue.lock().expect("build queue lock poisoned").pop_front();
+					let (pkgbase, _depth, _split) = match next {
+						Some(item) => item,
+						None => break,
+					};
+					let review_dir = rua_files::review_dir(dirs, pkgbase);
+					let build_dir = rua_files::build_dir(dirs, pkgbase);
+					rm_rf::force_remove_all(&build_dir).expect("Failed to remove old build dir");
+					std::fs::create_dir_all(&build_dir).expect("Failed to create build dir");
+					fs_extra::copy_items(
+						&vec![review_dir],
+						rua_files::global_build_dir(dirs),
+						&CopyOptions::new(),
+					)
+					.expect("failed to copy reviewed dir to build dir");
+					rm_rf::force_remove_all(build_dir.join(".git")).expect("Failed to remove .git");
+					wrapped::build_directory(
+						&build_dir.to_str().expect("Non-UTF8 directory name"),
+						dirs,
+						offline,
+						makepkg_options,
+						serialized,
+					);
+				});
+			}
+		});
 		for (pkgbase, _depth, _split) in &packages {
-			let review_dir = rua_files::review_dir(dirs, pkgbase);
-			let build_dir = rua_files::build_dir(dirs, pkgbase);
-			rm_rf::force_remove_all(&build_dir).expect("Failed to remove old build dir");
-			std::fs::create_dir_all(&build_dir).expect("Failed to create build dir");
-			fs_extra::copy_items(
-				&vec![review_dir],
-				rua_files::global_build_dir(dirs),
-				&CopyOptions::new(),
-			)
-			.expect("failed to copy reviewed dir to build dir");
-			rm_rf::force_remove_all(build_dir.join(".git")).expect("Failed to remove .git");
-			wrapped::build_directory(
-				&build_dir.to_str().expect("Non-UTF8 directory name"),
-				dirs,
-				offline,
-			);
-		}
-		for (pkgbase, _depth, _split) in &packages {
-			check_tars_and_move(pkgbase, dirs, &archive_whitelist);
+			check_tars_and_move(pkgbase, dirs, &archive_whitelist, noconfirm, report);
 		}
 		// This relation between split_name and the archive file is not actually correct here.
 		// Instead, all archive files of some group will be bound to one split name only here.
@@ -169,10 +250,34 @@ fn install_all(
 			}
 		}
 		pacman::ensure_aur_packages_installed(files_to_install, asdeps || depth > 0);
+		for (pkgbase, _depth, _split) in &packages {
+			let split_names = split_to_pkgbase
+				.iter()
+				.filter(|(_split, base)| *base == pkgbase)
+				.map(|(split, _base)| split.to_string())
+				.collect::<Vec<_>>();
+			let version = split_names
+				.iter()
+				.find_map(|split| split_to_version.get(split))
+				.expect("Internal error: package being installed has no known version");
+			rua_files::record_installed_package(
+				dirs,
+				pkgbase,
+				version,
+				split_names,
+				asdeps || depth > 0,
+			);
+		}
 	}
 }
 
-pub fn check_tars_and_move(name: &str, dirs: &ProjectDirs, archive_whitelist: &[String]) {
+pub fn check_tars_and_move(
+	name: &str,
+	dirs: &ProjectDirs,
+	archive_whitelist: &[String],
+	noconfirm: bool,
+	report: bool,
+) {
 	debug!("{}:{} checking tars for package {}", file!(), line!(), name);
 	let build_dir = rua_files::build_dir(dirs, name);
 	let dir_items: ReadDir = build_dir.read_dir().unwrap_or_else(|err| {
@@ -194,8 +299,15 @@ pub fn check_tars_and_move(name: &str, dirs: &ProjectDirs, archive_whitelist: &[
 		})
 		.collect::<Vec<_>>();
 	trace!("Files filtered for tar checking: {:?}", &dir_items);
+	let tar_check_mode = if report {
+		tar_check::TarCheckMode::Report
+	} else if noconfirm {
+		tar_check::TarCheckMode::NoConfirm
+	} else {
+		tar_check::TarCheckMode::Interactive
+	};
 	for file in dir_items.iter() {
-		tar_check::tar_check_unwrap(&file.path());
+		tar_check::tar_check_unwrap(&file.path(), tar_check_mode);
 	}
 	debug!("all package (tar) files checked, moving them");
 	let checked_tars_dir = rua_files::checked_tars_dir(dirs, name);
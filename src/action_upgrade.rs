@@ -0,0 +1,97 @@
+use crate::action_install;
+use crate::pacman;
+
+use indexmap::IndexMap;
+use itertools::Itertools;
+use log::debug;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// The AUR RPC caps the number of `arg[]` parameters accepted per request,
+/// so bulk lookups are issued in chunks this size and merged, instead of one
+/// request per name (too slow) or one request for everything (risks a 414 /
+/// truncated response on a machine with many foreign packages installed).
+const AUR_INFO_CHUNK_SIZE: usize = 200;
+
+/// Finds all foreign (non-repo) packages in the local alpm database,
+/// looks them up on AUR in bulk, and returns the split pkgnames whose
+/// installed version is older than the version currently on AUR.
+/// De-duplicated by pkgbase, so a split package isn't considered
+/// more than once.
+fn packages_to_upgrade() -> Vec<String> {
+	let alpm = pacman::create_alpm();
+	let local_db = alpm.localdb();
+	let foreign_packages = local_db
+		.pkgs()
+		.iter()
+		.filter(|pkg| {
+			!alpm
+				.syncdbs()
+				.iter()
+				.any(|sync_db| sync_db.pkg(pkg.name()).is_ok())
+		})
+		.collect_vec();
+
+	let names = foreign_packages.iter().map(|pkg| pkg.name()).collect_vec();
+	let name_to_aur: IndexMap<String, raur::Package> = names
+		.chunks(AUR_INFO_CHUNK_SIZE)
+		.flat_map(|chunk| {
+			raur::info(chunk).unwrap_or_else(|err| {
+				panic!("Failed to fetch info from AUR for installed packages, {}", err)
+			})
+		})
+		.map(|pkg| (pkg.name.clone(), pkg))
+		.collect();
+
+	let mut seen_pkgbases = HashSet::new();
+	let mut to_upgrade = Vec::new();
+	for pkg in foreign_packages {
+		let aur_pkg = match name_to_aur.get(pkg.name()) {
+			Some(aur_pkg) => aur_pkg,
+			// Not found on AUR: locally built or removed upstream, leave it alone.
+			None => continue,
+		};
+		if !seen_pkgbases.insert(aur_pkg.package_base.clone()) {
+			continue;
+		}
+		let installed_version = pkg.version();
+		let is_outdated = alpm::vercmp(installed_version.as_str(), aur_pkg.version.as_str())
+			== Ordering::Less;
+		if is_outdated {
+			debug!(
+				"{} is outdated: {} installed, {} on AUR",
+				pkg.name(),
+				installed_version,
+				aur_pkg.version
+			);
+			to_upgrade.push(pkg.name().to_string());
+		}
+	}
+	to_upgrade
+}
+
+pub fn upgrade(
+	dirs: &directories::ProjectDirs,
+	is_offline: bool,
+	asdeps: bool,
+	noconfirm: bool,
+	report: bool,
+	makepkg_options: &crate::wrapped::MakePkgOptions,
+	jobs: usize,
+) {
+	let targets = packages_to_upgrade();
+	if targets.is_empty() {
+		eprintln!("All AUR packages are up to date.");
+		return;
+	}
+	action_install::install(
+		&targets,
+		dirs,
+		is_offline,
+		asdeps,
+		noconfirm,
+		report,
+		makepkg_options,
+		jobs,
+	);
+}
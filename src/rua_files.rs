@@ -0,0 +1,90 @@
+use directories::ProjectDirs;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+pub fn review_dir(dirs: &ProjectDirs, pkgbase: &str) -> PathBuf {
+	dirs.data_dir().join("review").join(pkgbase)
+}
+
+pub fn global_build_dir(dirs: &ProjectDirs) -> PathBuf {
+	dirs.cache_dir().join("build")
+}
+
+pub fn build_dir(dirs: &ProjectDirs, pkgbase: &str) -> PathBuf {
+	global_build_dir(dirs).join(pkgbase)
+}
+
+pub fn checked_tars_dir(dirs: &ProjectDirs, pkgbase: &str) -> PathBuf {
+	dirs.cache_dir().join("checked_tars").join(pkgbase)
+}
+
+/// Authoritative record of a single AUR package built and installed by `rua`,
+/// independent of pacman's own database. Keyed by pkgbase in
+/// `InstalledPackages`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledPackageRecord {
+	pub version: String,
+	pub split_names: Vec<String>,
+	pub is_dependency: bool,
+	pub installed_at_unix_secs: u64,
+}
+
+pub type InstalledPackages = HashMap<String, InstalledPackageRecord>;
+
+fn installed_packages_path(dirs: &ProjectDirs) -> PathBuf {
+	dirs.data_dir().join("installed_packages.json")
+}
+
+pub fn load_installed_packages(dirs: &ProjectDirs) -> InstalledPackages {
+	let path = installed_packages_path(dirs);
+	let contents = match fs::read_to_string(&path) {
+		Ok(contents) => contents,
+		Err(_) => return InstalledPackages::new(),
+	};
+	serde_json::from_str(&contents).unwrap_or_else(|err| {
+		panic!("Failed to parse installed packages record {:?}, {}", path, err)
+	})
+}
+
+fn save_installed_packages(dirs: &ProjectDirs, packages: &InstalledPackages) {
+	let path = installed_packages_path(dirs);
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)
+			.unwrap_or_else(|err| panic!("Failed to create data dir {:?}, {}", parent, err));
+	}
+	let contents = serde_json::to_string_pretty(packages)
+		.unwrap_or_else(|err| panic!("Failed to serialize installed packages record, {}", err));
+	fs::write(&path, contents)
+		.unwrap_or_else(|err| panic!("Failed to write installed packages record {:?}, {}", path, err));
+}
+
+/// Records a successful `rua`-driven install of `pkgbase`, overwriting any
+/// previous record for it.
+pub fn record_installed_package(
+	dirs: &ProjectDirs,
+	pkgbase: &str,
+	version: &str,
+	split_names: Vec<String>,
+	is_dependency: bool,
+) {
+	let mut packages = load_installed_packages(dirs);
+	let installed_at_unix_secs = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_else(|err| panic!("System clock is before UNIX epoch, {}", err))
+		.as_secs();
+	packages.insert(
+		pkgbase.to_string(),
+		InstalledPackageRecord {
+			version: version.to_string(),
+			split_names,
+			is_dependency,
+			installed_at_unix_secs,
+		},
+	);
+	save_installed_packages(dirs, &packages);
+}
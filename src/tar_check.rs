@@ -2,6 +2,7 @@ use crate::terminal_util;
 
 use colored::*;
 use log::debug;
+use serde::Serialize;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
@@ -9,25 +10,50 @@ use std::path::PathBuf;
 use tar::*;
 use xz2::read::XzDecoder;
 
-pub fn tar_check_unwrap(tar_file: &Path) {
-	let result = tar_check(tar_file);
+/// Prefixes a well-behaved package is expected to install under. Anything
+/// else is a signal the PKGBUILD is doing something unusual.
+const EXPECTED_INSTALL_PREFIXES: &[&str] = &["usr/", "etc/", "opt/"];
+
+/// Paths that, if present in a package, are common signals of a malicious
+/// or badly broken PKGBUILD overwriting system-critical files.
+// Deliberately doesn't include paths like `usr/bin/sudo` or `usr/bin/su`: those are
+// legitimately owned and shipped by their own packages (sudo, util-linux), so exact-path
+// matching there would flag a correct install of those packages as malicious. A rogue
+// setuid drop under such a path is already caught by the SUID check in `tar_check_archive` instead.
+const CRITICAL_PATHS: &[&str] = &["etc/passwd", "etc/shadow", "etc/sudoers", "etc/ld.so.conf"];
+
+/// How `tar_check` should report its findings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TarCheckMode {
+	/// Prompt the user with the `[E]/[L]/[S]/[I]` menu, as before.
+	Interactive,
+	/// Auto-proceed, except SUID files or files at system-critical paths still
+	/// abort the install.
+	NoConfirm,
+	/// Print a machine-readable audit report on stdout instead of prompting,
+	/// then proceed or abort as in `NoConfirm`.
+	Report,
+}
+
+pub fn tar_check_unwrap(tar_file: &Path, mode: TarCheckMode) {
+	let result = tar_check(tar_file, mode);
 	result.unwrap_or_else(|err| {
 		eprintln!("{}", err);
 		std::process::exit(1)
 	})
 }
 
-pub fn tar_check(tar_file: &Path) -> Result<(), String> {
+pub fn tar_check(tar_file: &Path, mode: TarCheckMode) -> Result<(), String> {
 	let tar_str = tar_file
 		.to_str()
 		.unwrap_or_else(|| panic!("{}:{} Failed to parse tar file name", file!(), line!()));
 	let archive = File::open(&tar_file).unwrap_or_else(|_| panic!("cannot open file {}", tar_str));
 	if tar_str.ends_with(".tar.xz") {
-		tar_check_archive(Archive::new(XzDecoder::new(archive)), tar_str);
+		tar_check_archive(Archive::new(XzDecoder::new(archive)), tar_str, mode);
 		debug!("Checked package tar file {}", tar_str);
 		Ok(())
 	} else if tar_str.ends_with(".tar") {
-		tar_check_archive(Archive::new(archive), tar_str);
+		tar_check_archive(Archive::new(archive), tar_str, mode);
 		debug!("Checked package tar file {}", tar_str);
 		Ok(())
 	} else {
@@ -38,11 +64,24 @@ pub fn tar_check(tar_file: &Path) -> Result<(), String> {
 	}
 }
 
-fn tar_check_archive<R: Read>(mut archive: Archive<R>, path_str: &str) {
+#[derive(Serialize)]
+struct TarAuditReport<'a> {
+	archive: &'a str,
+	all_files: &'a [String],
+	executable_files: &'a [String],
+	suid_files: &'a [String],
+	outside_expected_prefix_files: &'a [String],
+	critical_path_files: &'a [String],
+	install_script: Option<&'a str>,
+}
+
+fn tar_check_archive<R: Read>(mut archive: Archive<R>, path_str: &str, mode: TarCheckMode) {
 	let mut install_file = String::new();
 	let mut all_files = Vec::new();
 	let mut executable_files = Vec::new();
 	let mut suid_files = Vec::new();
+	let mut outside_expected_prefix_files = Vec::new();
+	let mut critical_path_files = Vec::new();
 	let archive_files = archive
 		.entries()
 		.unwrap_or_else(|e| panic!("cannot open archive {}, {}", path_str, e));
@@ -60,7 +99,7 @@ fn tar_check_archive<R: Read>(mut archive: Archive<R>, path_str: &str) {
 				.unwrap_or_else(|| panic!("{}:{} failed to parse file name", file!(), line!()))
 				.to_owned()
 		};
-		let mode = file.header().mode().unwrap_or_else(|_| {
+		let mode_bits = file.header().mode().unwrap_or_else(|_| {
 			panic!(
 				"{}:{} Failed to get file mode for file {}",
 				file!(),
@@ -71,11 +110,20 @@ fn tar_check_archive<R: Read>(mut archive: Archive<R>, path_str: &str) {
 		let is_normal = !path.ends_with('/') && !path.starts_with('.');
 		if is_normal {
 			all_files.push(path.clone());
+			if !EXPECTED_INSTALL_PREFIXES
+				.iter()
+				.any(|prefix| path.starts_with(prefix))
+			{
+				outside_expected_prefix_files.push(path.clone());
+			}
+			if CRITICAL_PATHS.contains(&path.as_str()) {
+				critical_path_files.push(path.clone());
+			}
 		}
-		if is_normal && (mode & 0o111 > 0) {
+		if is_normal && (mode_bits & 0o111 > 0) {
 			executable_files.push(path.clone());
 		}
-		if mode > 0o777 {
+		if mode_bits > 0o777 {
 			suid_files.push(path.clone());
 		}
 		if &path == ".INSTALL" {
@@ -86,6 +134,56 @@ fn tar_check_archive<R: Read>(mut archive: Archive<R>, path_str: &str) {
 	}
 
 	let has_install = !install_file.is_empty();
+
+	if mode == TarCheckMode::Report {
+		let report = TarAuditReport {
+			archive: path_str,
+			all_files: &all_files,
+			executable_files: &executable_files,
+			suid_files: &suid_files,
+			outside_expected_prefix_files: &outside_expected_prefix_files,
+			critical_path_files: &critical_path_files,
+			install_script: if has_install {
+				Some(install_file.as_str())
+			} else {
+				None
+			},
+		};
+		println!(
+			"{}",
+			serde_json::to_string_pretty(&report)
+				.unwrap_or_else(|e| panic!("Failed to serialize audit report for {}, {}", path_str, e))
+		);
+	}
+
+	if mode == TarCheckMode::NoConfirm || mode == TarCheckMode::Report {
+		if !suid_files.is_empty() {
+			eprintln!(
+				"{}",
+				format!("Package {} contains SUID files, refusing to auto-confirm:", path_str).red()
+			);
+			for path in &suid_files {
+				eprintln!("{}", path);
+			}
+			std::process::exit(1);
+		}
+		if !critical_path_files.is_empty() {
+			eprintln!(
+				"{}",
+				format!(
+					"Package {} installs to system-critical paths, refusing to auto-confirm:",
+					path_str
+				)
+				.red()
+			);
+			for path in &critical_path_files {
+				eprintln!("{}", path);
+			}
+			std::process::exit(1);
+		}
+		return;
+	}
+
 	loop {
 		if suid_files.is_empty() {
 			eprint!("Package {} has no SUID files.\n", path_str);
@@ -100,6 +198,12 @@ fn tar_check_archive<R: Read>(mut archive: Archive<R>, path_str: &str) {
 		if !suid_files.is_empty() {
 			eprint!("{}", "!!! [S]=list SUID files!!!, ".red())
 		};
+		if !critical_path_files.is_empty() {
+			eprint!("{}", "!!! [C]=list system-critical paths!!!, ".red())
+		};
+		if !outside_expected_prefix_files.is_empty() {
+			eprint!("[P]=list out-of-prefix files, ")
+		};
 		eprint!("[O]=ok, proceed. ");
 		let string = terminal_util::read_line_lowercase();
 		eprintln!();
@@ -107,6 +211,14 @@ fn tar_check_archive<R: Read>(mut archive: Archive<R>, path_str: &str) {
 			for path in &suid_files {
 				eprintln!("{}", path);
 			}
+		} else if string == "c" && !critical_path_files.is_empty() {
+			for path in &critical_path_files {
+				eprintln!("{}", path);
+			}
+		} else if string == "p" && !outside_expected_prefix_files.is_empty() {
+			for path in &outside_expected_prefix_files {
+				eprintln!("{}", path);
+			}
 		} else if string == "e" {
 			for path in &executable_files {
 				eprintln!("{}", path);
@@ -0,0 +1,77 @@
+use directories::ProjectDirs;
+use std::process::Command;
+
+/// Options controlling how `makepkg` is invoked for a given build directory.
+/// Collecting them here keeps call sites from string-concatenating makepkg
+/// arguments by hand.
+#[derive(Debug, Clone, Default)]
+pub struct MakePkgOptions {
+	pub clean: bool,
+	pub no_deps: bool,
+	pub skip_pgp: bool,
+	/// `makepkg --needed` only has an effect alongside `-i/--install`, which
+	/// `rua` never passes, so it would be a no-op makepkg flag. Instead
+	/// `install_all` checks `rua_files`'s persisted install record itself
+	/// and skips building a pkgbase already at the target version.
+	pub needed: bool,
+	pub no_prepare: bool,
+	pub as_deps: bool,
+}
+
+fn makepkg_args(options: &MakePkgOptions, serialized: bool) -> Vec<&'static str> {
+	let mut args = vec!["--noconfirm"];
+	// --syncdeps/--rmdeps make makepkg call into pacman to install and then remove
+	// build dependencies, which takes the pacman db lock. When several builds run
+	// concurrently within the same depth group that would make them contend on the
+	// lock (and --rmdeps could even remove a dependency a sibling build still needs),
+	// so only do it when this is the sole in-flight build.
+	//
+	// Dropping these flags under concurrency is only safe because
+	// `pacman::ensure_pacman_packages_installed` is called (from `install`, before
+	// `install_all` ever runs) with the *full* `pacman_deps` set coming out of
+	// `aur_rpc_utils::recursive_info`, which includes makedepends, not only runtime
+	// deps. If that invariant ever changes, a `--jobs >1` build can start missing
+	// makedepends that `--syncdeps` used to paper over, while `--jobs 1` keeps working.
+	if serialized {
+		args.push("--syncdeps");
+		args.push("--rmdeps");
+	}
+	if options.clean {
+		args.push("--clean");
+	}
+	if options.no_deps {
+		args.push("--nodeps");
+	}
+	if options.skip_pgp {
+		args.push("--skippgpcheck");
+	}
+	if options.no_prepare {
+		args.push("--noprepare");
+	}
+	if options.as_deps {
+		args.push("--asdeps");
+	}
+	args
+}
+
+pub fn build_directory(
+	dir: &str,
+	_dirs: &ProjectDirs,
+	offline: bool,
+	options: &MakePkgOptions,
+	serialized: bool,
+) {
+	let mut command = Command::new("makepkg");
+	command.current_dir(dir);
+	command.args(makepkg_args(options, serialized));
+	if offline {
+		command.env("RUA_OFFLINE", "1");
+	}
+	let result = command
+		.status()
+		.unwrap_or_else(|err| panic!("Failed to run makepkg in {}, {}", dir, err));
+	if !result.success() {
+		eprintln!("makepkg exited with an error in {}", dir);
+		std::process::exit(result.code().unwrap_or(1));
+	}
+}